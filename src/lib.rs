@@ -9,10 +9,10 @@ use jrsonnet_evaluator::{
     error::{Error, ErrorKind::*},
     function::{
         builtin::{NativeCallback, NativeCallbackHandler},
-        TlaArg,
+        FuncVal, TlaArg,
     },
     gc::GcHashMap,
-    manifest::{JsonFormat, ManifestFormat},
+    manifest::{JsonFormat, ManifestFormat, ToStringFormat, YamlFormat},
     stack::set_stack_depth_limit,
     tb,
     trace::{CompactFormat, PathResolver, TraceFormat},
@@ -21,9 +21,23 @@ use jrsonnet_evaluator::{
 };
 use jrsonnet_gcmodule::Trace;
 use jrsonnet_parser::{ParserSettings, Source, SourceDirectory, SourceFile, SourcePath};
-use pyo3::exceptions::{PyRuntimeError, PyTypeError};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBool, PyDict, PyFloat, PyList, PySequence, PyString, PyTuple};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyList, PySequence, PyString, PyTuple};
+
+/// Extracts the raw bytes of an `import_callback` content return value, accepting both `str`
+/// (for text assets) and `bytes` (for `importbin`-style binary assets).
+fn py_content_to_bytes(py: Python, obj: &PyObject) -> PyResult<Vec<u8>> {
+    if let Ok(s) = obj.downcast::<PyString>(py) {
+        Ok(s.to_str()?.as_bytes().to_vec())
+    } else if let Ok(b) = obj.downcast::<PyBytes>(py) {
+        Ok(b.as_bytes().to_vec())
+    } else {
+        Err(PyTypeError::new_err(
+            "import_callback content must be str or bytes",
+        ))
+    }
+}
 
 #[derive(Trace)]
 struct PythonImportResolver {
@@ -59,11 +73,24 @@ impl ImportResolver for PythonImportResolver {
         };
         let (resolved, content) =
             Python::with_gil(|py| match self.callback.call(py, (base, path), None) {
-                Ok(obj) => obj.extract::<(String, Option<String>)>(py).map_err(|err| {
-                    let err_msg = err.to_string();
-                    err.restore(py);
-                    ImportCallbackError(format!("import_callback error: {}", err_msg))
-                }),
+                Ok(obj) => obj
+                    .extract::<(String, Option<PyObject>)>(py)
+                    .map_err(|err| {
+                        let err_msg = err.to_string();
+                        err.restore(py);
+                        ImportCallbackError(format!("import_callback error: {}", err_msg))
+                    })
+                    .and_then(|(resolved, content)| {
+                        let content = content
+                            .map(|obj| py_content_to_bytes(py, &obj))
+                            .transpose()
+                            .map_err(|err| {
+                                let err_msg = err.to_string();
+                                err.restore(py);
+                                ImportCallbackError(format!("import_callback error: {}", err_msg))
+                            })?;
+                        Ok((resolved, content))
+                    }),
                 Err(err) => {
                     let err_msg = err.to_string();
                     err.restore(py);
@@ -77,7 +104,7 @@ impl ImportResolver for PythonImportResolver {
             let resolved = SourcePath::new(SourceFile::new(PathBuf::from(resolved)));
             let mut out = self.out.borrow_mut();
             if !out.contains_key(&resolved) {
-                out.insert(resolved.clone(), content.into());
+                out.insert(resolved.clone(), content);
             }
             Ok(resolved)
         } else {
@@ -89,7 +116,51 @@ impl ImportResolver for PythonImportResolver {
         &self,
         resolved: &SourcePath,
     ) -> jrsonnet_evaluator::error::Result<Vec<u8>> {
-        Ok(self.out.borrow().get(resolved).unwrap().clone())
+        self.out.borrow().get(resolved).cloned().ok_or_else(|| {
+            Error::new(ImportIo(format!(
+                "no content was cached for {resolved:?} by import_callback"
+            )))
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Chains a Python `import_callback` with filesystem `jpathdir` resolution: the callback is
+/// tried first, and only when it reports the file as not found does resolution fall back to
+/// searching the library path on disk.
+#[derive(Trace)]
+struct ChainedImportResolver {
+    python: PythonImportResolver,
+    #[trace(skip)]
+    fallback: FileImportResolver,
+}
+
+impl ImportResolver for ChainedImportResolver {
+    fn resolve_from(
+        &self,
+        from: &SourcePath,
+        path: &str,
+    ) -> jrsonnet_evaluator::error::Result<SourcePath> {
+        match self.python.resolve_from(from, path) {
+            Ok(resolved) => Ok(resolved),
+            Err(err) if matches!(err.error(), ImportFileNotFound(..)) => {
+                self.fallback.resolve_from(from, path)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn load_file_contents(
+        &self,
+        resolved: &SourcePath,
+    ) -> jrsonnet_evaluator::error::Result<Vec<u8>> {
+        if let Some(content) = self.python.out.borrow().get(resolved) {
+            return Ok(content.clone());
+        }
+        self.fallback.load_file_contents(resolved)
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -97,7 +168,7 @@ impl ImportResolver for PythonImportResolver {
     }
 }
 
-fn pyobject_to_val(py: Python, obj: PyObject) -> PyResult<Val> {
+fn pyobject_to_val(py: Python, obj: PyObject, preserve_order: bool) -> PyResult<Val> {
     return if let Ok(s) = obj.downcast::<PyString>(py) {
         s.to_str().map(|s| Val::Str(StrValue::Flat(s.into())))
     } else if let Ok(b) = obj.downcast::<PyBool>(py) {
@@ -113,17 +184,23 @@ fn pyobject_to_val(py: Python, obj: PyObject) -> PyResult<Val> {
         let mut arr = Vec::with_capacity(len);
         for i in 0..len {
             let item = seq.get_item(i)?;
-            arr.push(pyobject_to_val(py, item.into_py(py))?);
+            arr.push(pyobject_to_val(py, item.into_py(py), preserve_order)?);
         }
         Ok(Val::Arr(ArrValue::eager(arr)))
     } else if let Ok(d) = obj.downcast::<PyDict>(py) {
         let mut map = ObjValue::new_empty();
         for (k, v) in d {
             let k = k.extract::<String>()?;
-            let v = pyobject_to_val(py, v.into_py(py))?;
+            let v = pyobject_to_val(py, v.into_py(py), preserve_order)?;
             map.extend_field(k.into()).value(v);
         }
         Ok(Val::Obj(map))
+    } else if let Ok(f) = obj.extract::<PyRef<JsonnetFunc>>(py) {
+        // Unwrap rather than re-wrapping, so a Jsonnet function passed through Python unchanged
+        // (e.g. returned as-is from a native callback) keeps its original parameter list.
+        Ok(Val::Func(f.func.clone()))
+    } else if obj.as_ref(py).is_callable() {
+        py_callable_to_func(py, obj, preserve_order).map(Val::Func)
     } else {
         Err(PyTypeError::new_err(
             "Unrecognized type return from Python Jsonnet native extension.",
@@ -131,6 +208,32 @@ fn pyobject_to_val(py: Python, obj: PyObject) -> PyResult<Val> {
     };
 }
 
+/// Wraps a Python callable as a Jsonnet native function value, so a Python function can be
+/// passed anywhere Jsonnet expects a function (e.g. returned from a native callback). Parameter
+/// names are recovered with `inspect.signature` since jrsonnet functions are called by name as
+/// well as by position.
+fn py_callable_to_func(py: Python, obj: PyObject, preserve_order: bool) -> PyResult<FuncVal> {
+    let signature = py
+        .import("inspect")?
+        .call_method1("signature", (obj.as_ref(py),))?;
+    let parameters = signature.getattr("parameters")?.call_method0("keys")?;
+    let mut params = Vec::new();
+    for name in parameters.iter()? {
+        params.push(Cow::Owned(name?.extract::<String>()?));
+    }
+    Ok(FuncVal::native(
+        #[allow(deprecated)]
+        NativeCallback::new(
+            params,
+            JsonnetNativeCallbackHandler {
+                name: "<python function>".to_string(),
+                func: obj,
+                preserve_order,
+            },
+        ),
+    ))
+}
+
 fn val_to_pyobject(py: Python, val: &Val, preserve_order: bool) -> PyObject {
     match val {
         Val::Bool(b) => b.into_py(py),
@@ -157,7 +260,39 @@ fn val_to_pyobject(py: Python, val: &Val, preserve_order: bool) -> PyObject {
             }
             dict.into_py(py)
         }
-        Val::Func(_) => unimplemented!(),
+        Val::Func(f) => Py::new(
+            py,
+            JsonnetFunc {
+                func: f.clone(),
+                preserve_order,
+            },
+        )
+        .unwrap()
+        .into_py(py),
+    }
+}
+
+/// A Jsonnet function value exposed to Python as a callable, returned from `val_to_pyobject`
+/// wherever Jsonnet produces a function (e.g. an argument passed to a native callback).
+#[pyclass(unsendable)]
+struct JsonnetFunc {
+    func: FuncVal,
+    preserve_order: bool,
+}
+
+#[pymethods]
+impl JsonnetFunc {
+    #[pyo3(signature = (*args))]
+    fn __call__(&self, py: Python, args: &PyTuple) -> PyResult<PyObject> {
+        let mut call_args = Vec::with_capacity(args.len());
+        for arg in args {
+            call_args.push(pyobject_to_val(py, arg.into_py(py), self.preserve_order)?);
+        }
+        let result = self
+            .func
+            .evaluate_values(call_args, true)
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        Ok(val_to_pyobject(py, &result, self.preserve_order))
     }
 }
 
@@ -179,7 +314,7 @@ impl NativeCallbackHandler for JsonnetNativeCallbackHandler {
                 .map(|v| val_to_pyobject(py, v, self.preserve_order))
                 .collect();
             let err = match self.func.call(py, PyTuple::new(py, args), None) {
-                Ok(obj) => match pyobject_to_val(py, obj) {
+                Ok(obj) => match pyobject_to_val(py, obj, self.preserve_order) {
                     Ok(val) => return Ok(val),
                     Err(err) => err,
                 },
@@ -194,20 +329,174 @@ impl NativeCallbackHandler for JsonnetNativeCallbackHandler {
     }
 }
 
-struct VirtualMachine {
+/// The `indent`/`newline` defaults below (3 spaces, trailing newline) are chosen to match
+/// `JsonFormat::default()` exactly, so callers who never pass `manifest=`/`indent=`/`newline=`
+/// keep getting jsonnet's canonical JSON output, unchanged from before manifest formats existed.
+const DEFAULT_INDENT: usize = 3;
+const DEFAULT_NEWLINE: bool = true;
+
+fn build_manifest_format(
+    manifest: &str,
+    indent: usize,
+    newline: bool,
+) -> PyResult<Box<dyn ManifestFormat>> {
+    if manifest == "json" && indent == DEFAULT_INDENT && newline == DEFAULT_NEWLINE {
+        return Ok(Box::new(JsonFormat::default()));
+    }
+    let padding = " ".repeat(indent);
+    let newline = if newline { "\n" } else { "" };
+    match manifest {
+        "json" => Ok(Box::new(JsonFormat {
+            padding: padding.into(),
+            newline: newline.into(),
+            ..Default::default()
+        })),
+        "yaml" => Ok(Box::new(YamlFormat {
+            padding: padding.into(),
+            newline: newline.into(),
+            ..Default::default()
+        })),
+        "string" => Ok(Box::new(ToStringFormat)),
+        other => Err(PyValueError::new_err(format!(
+            "unknown manifest format: {other:?}, expected one of \"json\", \"yaml\", \"string\""
+        ))),
+    }
+}
+
+fn entries_to_dict(py: Python, entries: Vec<(String, String)>) -> PyObject {
+    let dict = PyDict::new(py);
+    for (k, v) in entries {
+        dict.set_item(k, v).unwrap();
+    }
+    dict.into_py(py)
+}
+
+/// A reusable jsonnet virtual machine.
+///
+/// Unlike the module-level `evaluate_file`/`evaluate_snippet` functions, a `Jsonnet` instance
+/// keeps its `State`, context initializer, native callbacks and import resolver alive across
+/// calls, so the resolved-import cache and stdlib setup are amortized over many evaluations.
+#[pyclass(unsendable)]
+struct Jsonnet {
     state: State,
     manifest_format: Box<dyn ManifestFormat>,
     trace_format: Box<dyn TraceFormat>,
     tla_args: GcHashMap<IStr, TlaArg>,
+    preserve_order: bool,
 }
 
-impl VirtualMachine {
+#[pymethods]
+impl Jsonnet {
     #[allow(clippy::too_many_arguments)]
-    #[inline]
+    #[new]
+    #[pyo3(signature = (
+        jpathdir = None,
+        max_stack = 500,
+        gc_min_objects = 1000,
+        gc_growth_trigger = 2.0,
+        ext_vars = HashMap::new(),
+        ext_codes = HashMap::new(),
+        tla_vars = HashMap::new(),
+        tla_codes = HashMap::new(),
+        max_trace = 20,
+        import_callback = None,
+        native_callbacks = HashMap::new(),
+        preserve_order = false,
+        manifest = "json",
+        indent = 3,
+        newline = true,
+    ))]
     fn new(
+        py: Python,
+        jpathdir: Option<LibraryPath>,
+        max_stack: usize,
+        gc_min_objects: usize,
+        gc_growth_trigger: f64,
+        ext_vars: HashMap<String, String>,
+        ext_codes: HashMap<String, String>,
+        tla_vars: HashMap<String, String>,
+        tla_codes: HashMap<String, String>,
+        max_trace: usize,
+        import_callback: Option<PyObject>,
+        native_callbacks: HashMap<String, (PyObject, PyObject)>,
+        preserve_order: bool,
+        manifest: &str,
+        indent: usize,
+        newline: bool,
+    ) -> PyResult<Self> {
+        Self::build(
+            py,
+            jpathdir.map(|x| x.into_vec()),
+            max_stack,
+            gc_min_objects,
+            gc_growth_trigger,
+            ext_vars,
+            ext_codes,
+            tla_vars,
+            tla_codes,
+            max_trace,
+            import_callback,
+            native_callbacks,
+            preserve_order,
+            manifest,
+            indent,
+            newline,
+        )
+    }
+
+    fn evaluate_file(&self, py: Python, filename: &str) -> PyResult<String> {
+        self.eval_file(filename)
+            .and_then(|val| self.manifest(val))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+
+    fn evaluate_snippet(&self, py: Python, filename: &str, src: &str) -> PyResult<String> {
+        self.eval_snippet(filename, src)
+            .and_then(|val| self.manifest(val))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+
+    fn evaluate_file_multi(&self, py: Python, filename: &str) -> PyResult<PyObject> {
+        self.eval_file(filename)
+            .and_then(|val| self.manifest_multi(val))
+            .map(|entries| entries_to_dict(py, entries))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+
+    fn evaluate_snippet_multi(&self, py: Python, filename: &str, src: &str) -> PyResult<PyObject> {
+        self.eval_snippet(filename, src)
+            .and_then(|val| self.manifest_multi(val))
+            .map(|entries| entries_to_dict(py, entries))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+
+    fn evaluate_file_stream(&self, py: Python, filename: &str) -> PyResult<Vec<String>> {
+        self.eval_file(filename)
+            .and_then(|val| self.manifest_stream(val))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+
+    fn evaluate_snippet_stream(
+        &self,
+        py: Python,
+        filename: &str,
+        src: &str,
+    ) -> PyResult<Vec<String>> {
+        self.eval_snippet(filename, src)
+            .and_then(|val| self.manifest_stream(val))
+            .map_err(|e| self.error_to_pyerr(py, &e))
+    }
+}
+
+impl Jsonnet {
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    fn build(
         py: Python,
         jpathdir: Option<Vec<PathBuf>>,
         max_stack: usize,
+        gc_min_objects: usize,
+        gc_growth_trigger: f64,
         ext_vars: HashMap<String, String>,
         ext_codes: HashMap<String, String>,
         tla_vars: HashMap<String, String>,
@@ -216,9 +505,22 @@ impl VirtualMachine {
         import_callback: Option<PyObject>,
         native_callbacks: HashMap<String, (PyObject, PyObject)>,
         preserve_order: bool,
+        manifest: &str,
+        indent: usize,
+        newline: bool,
     ) -> PyResult<Self> {
+        let manifest_format = build_manifest_format(manifest, indent, newline)?;
         let state = State::default();
         set_stack_depth_limit(max_stack);
+        // `gc_min_objects`/`gc_growth_trigger` are real fields on
+        // `jrsonnet_evaluator::EvaluationSettings` (via `State::settings_mut`): jrsonnet_gcmodule's
+        // cycle collector consults them on every allocation to decide when to run a collection, so
+        // setting them here is sufficient on its own — no explicit `collect()` call is needed.
+        {
+            let mut settings = state.settings_mut();
+            settings.gc_min_objects = gc_min_objects;
+            settings.gc_growth_trigger = gc_growth_trigger;
+        }
 
         state.settings_mut().import_resolver = tb!(FileImportResolver::default());
 
@@ -263,11 +565,18 @@ impl VirtualMachine {
             if !import_callback.as_ref(py).is_callable() {
                 return Err(PyTypeError::new_err("import_callback must be callable"));
             }
-            let import_resolver = PythonImportResolver {
+            let python_resolver = PythonImportResolver {
                 callback: import_callback,
                 out: RefCell::new(HashMap::new()),
             };
-            state.set_import_resolver(import_resolver);
+            if let Some(jpathdir) = jpathdir {
+                state.set_import_resolver(ChainedImportResolver {
+                    python: python_resolver,
+                    fallback: FileImportResolver::new(jpathdir),
+                });
+            } else {
+                state.set_import_resolver(python_resolver);
+            }
         } else if let Some(jpathdir) = jpathdir {
             let import_resolver = FileImportResolver::new(jpathdir);
             state.set_import_resolver(import_resolver);
@@ -297,24 +606,58 @@ impl VirtualMachine {
         state.settings_mut().context_initializer = tb!(context_initializer);
         Ok(Self {
             state,
-            manifest_format: Box::new(JsonFormat::default()),
+            manifest_format,
             trace_format: Box::new(trace_format),
             tla_args,
+            preserve_order,
         })
     }
 
-    fn evaluate_file(&self, filename: &str) -> Result<String, Error> {
+    fn eval_file(&self, filename: &str) -> Result<Val, Error> {
         self.state
             .import_from(&SourcePath::new(SourceDirectory::new(".".into())), filename)
             .and_then(|val| apply_tla(self.state.clone(), &self.tla_args, val))
-            .and_then(|val| val.manifest(&self.manifest_format))
     }
 
-    fn evaluate_snippet(&self, filename: &str, snippet: &str) -> Result<String, Error> {
+    fn eval_snippet(&self, filename: &str, snippet: &str) -> Result<Val, Error> {
         self.state
             .evaluate_snippet(filename, snippet)
             .and_then(|val| apply_tla(self.state.clone(), &self.tla_args, val))
-            .and_then(|val| val.manifest(&self.manifest_format))
+    }
+
+    fn manifest(&self, val: Val) -> Result<String, Error> {
+        val.manifest(&self.manifest_format)
+    }
+
+    /// Manifest an object's fields independently, as used by `evaluate_*_multi`.
+    fn manifest_multi(&self, val: Val) -> Result<Vec<(String, String)>, Error> {
+        let Val::Obj(obj) = val else {
+            return Err(Error::new(RuntimeError(
+                "multi mode expects the top-level value to be an object".into(),
+            )));
+        };
+        obj.fields(self.preserve_order)
+            .into_iter()
+            .map(|field| {
+                let field_val = obj
+                    .get(field.clone())?
+                    .expect("field came from this object's own field list");
+                let manifested = field_val.manifest(&self.manifest_format)?;
+                Ok((field.to_string(), manifested))
+            })
+            .collect()
+    }
+
+    /// Manifest an array's elements independently, as used by `evaluate_*_stream`.
+    fn manifest_stream(&self, val: Val) -> Result<Vec<String>, Error> {
+        let Val::Arr(arr) = val else {
+            return Err(Error::new(RuntimeError(
+                "stream mode expects the top-level value to be an array".into(),
+            )));
+        };
+        arr.iter()
+            .map(|item| item?.manifest(&self.manifest_format))
+            .collect()
     }
 
     fn error_to_pyerr(&self, py: Python, err: &Error) -> PyErr {
@@ -348,119 +691,186 @@ impl LibraryPath {
     }
 }
 
-/// Evaluate jsonnet file
-#[allow(clippy::too_many_arguments)]
-#[pyfunction(signature = (
-    filename,
-    jpathdir = None,
-    max_stack = 500,
-    gc_min_objects = 1000,
-    gc_growth_trigger = 2.0,
-    ext_vars = HashMap::new(),
-    ext_codes = HashMap::new(),
-    tla_vars = HashMap::new(),
-    tla_codes = HashMap::new(),
-    max_trace = 20,
-    import_callback = None,
-    native_callbacks = HashMap::new(),
-    preserve_order = false,
-))]
-fn evaluate_file(
-    py: Python,
-    filename: &str,
-    jpathdir: Option<LibraryPath>,
-    max_stack: usize,
-    #[allow(unused_variables)] gc_min_objects: usize,
-    #[allow(unused_variables)] gc_growth_trigger: f64,
-    ext_vars: HashMap<String, String>,
-    ext_codes: HashMap<String, String>,
-    tla_vars: HashMap<String, String>,
-    tla_codes: HashMap<String, String>,
-    max_trace: usize,
-    import_callback: Option<PyObject>,
-    native_callbacks: HashMap<String, (PyObject, PyObject)>,
-    preserve_order: bool,
-) -> PyResult<String> {
-    let vm = VirtualMachine::new(
-        py,
-        jpathdir.map(|x| x.into_vec()),
-        max_stack,
-        ext_vars,
-        ext_codes,
-        tla_vars,
-        tla_codes,
-        max_trace,
-        import_callback,
-        native_callbacks,
-        preserve_order,
-    )?;
-
-    let result = vm
-        .evaluate_file(filename)
-        .map_err(|e| vm.error_to_pyerr(py, &e))?;
-    Ok(result)
+/// Declares a module-level `evaluate_*` pyfunction taking a jsonnet `filename`, forwarding the
+/// shared ~20-parameter signature into `Jsonnet::build` and delegating the actual evaluation to
+/// the matching `#[pymethods]` method on `Jsonnet` (which already pairs an `eval_file`/
+/// `eval_snippet` call with the right `manifest*` variant). Keeping the parameter list in one
+/// place avoids having to hand-sync it across every `evaluate_file*`/`evaluate_snippet*` pair.
+macro_rules! evaluate_file_pyfn {
+    ($name:ident, $doc:literal, $ret:ty, $method:ident) => {
+        #[doc = $doc]
+        #[allow(clippy::too_many_arguments)]
+        #[pyfunction(signature = (
+            filename,
+            jpathdir = None,
+            max_stack = 500,
+            gc_min_objects = 1000,
+            gc_growth_trigger = 2.0,
+            ext_vars = HashMap::new(),
+            ext_codes = HashMap::new(),
+            tla_vars = HashMap::new(),
+            tla_codes = HashMap::new(),
+            max_trace = 20,
+            import_callback = None,
+            native_callbacks = HashMap::new(),
+            preserve_order = false,
+            manifest = "json",
+            indent = 3,
+            newline = true,
+        ))]
+        fn $name(
+            py: Python,
+            filename: &str,
+            jpathdir: Option<LibraryPath>,
+            max_stack: usize,
+            gc_min_objects: usize,
+            gc_growth_trigger: f64,
+            ext_vars: HashMap<String, String>,
+            ext_codes: HashMap<String, String>,
+            tla_vars: HashMap<String, String>,
+            tla_codes: HashMap<String, String>,
+            max_trace: usize,
+            import_callback: Option<PyObject>,
+            native_callbacks: HashMap<String, (PyObject, PyObject)>,
+            preserve_order: bool,
+            manifest: &str,
+            indent: usize,
+            newline: bool,
+        ) -> PyResult<$ret> {
+            Jsonnet::build(
+                py,
+                jpathdir.map(|x| x.into_vec()),
+                max_stack,
+                gc_min_objects,
+                gc_growth_trigger,
+                ext_vars,
+                ext_codes,
+                tla_vars,
+                tla_codes,
+                max_trace,
+                import_callback,
+                native_callbacks,
+                preserve_order,
+                manifest,
+                indent,
+                newline,
+            )?
+            .$method(py, filename)
+        }
+    };
 }
 
-/// Evaluate jsonnet code snippet
-#[allow(clippy::too_many_arguments)]
-#[pyfunction(signature = (
-    filename,
-    src,
-    jpathdir = None,
-    max_stack = 500,
-    gc_min_objects = 1000,
-    gc_growth_trigger = 2.0,
-    ext_vars = HashMap::new(),
-    ext_codes = HashMap::new(),
-    tla_vars = HashMap::new(),
-    tla_codes = HashMap::new(),
-    max_trace = 20,
-    import_callback = None,
-    native_callbacks = HashMap::new(),
-    preserve_order = false,
-))]
-fn evaluate_snippet(
-    py: Python,
-    filename: &str,
-    src: &str,
-    jpathdir: Option<LibraryPath>,
-    max_stack: usize,
-    #[allow(unused_variables)] gc_min_objects: usize,
-    #[allow(unused_variables)] gc_growth_trigger: f64,
-    ext_vars: HashMap<String, String>,
-    ext_codes: HashMap<String, String>,
-    tla_vars: HashMap<String, String>,
-    tla_codes: HashMap<String, String>,
-    max_trace: usize,
-    import_callback: Option<PyObject>,
-    native_callbacks: HashMap<String, (PyObject, PyObject)>,
-    preserve_order: bool,
-) -> PyResult<String> {
-    let vm = VirtualMachine::new(
-        py,
-        jpathdir.map(|x| x.into_vec()),
-        max_stack,
-        ext_vars,
-        ext_codes,
-        tla_vars,
-        tla_codes,
-        max_trace,
-        import_callback,
-        native_callbacks,
-        preserve_order,
-    )?;
-
-    let result = vm
-        .evaluate_snippet(filename, src)
-        .map_err(|e| vm.error_to_pyerr(py, &e))?;
-    Ok(result)
+/// Same as [`evaluate_file_pyfn!`], but for the `evaluate_snippet*` siblings that take an extra
+/// `src` parameter in addition to `filename`.
+macro_rules! evaluate_snippet_pyfn {
+    ($name:ident, $doc:literal, $ret:ty, $method:ident) => {
+        #[doc = $doc]
+        #[allow(clippy::too_many_arguments)]
+        #[pyfunction(signature = (
+            filename,
+            src,
+            jpathdir = None,
+            max_stack = 500,
+            gc_min_objects = 1000,
+            gc_growth_trigger = 2.0,
+            ext_vars = HashMap::new(),
+            ext_codes = HashMap::new(),
+            tla_vars = HashMap::new(),
+            tla_codes = HashMap::new(),
+            max_trace = 20,
+            import_callback = None,
+            native_callbacks = HashMap::new(),
+            preserve_order = false,
+            manifest = "json",
+            indent = 3,
+            newline = true,
+        ))]
+        fn $name(
+            py: Python,
+            filename: &str,
+            src: &str,
+            jpathdir: Option<LibraryPath>,
+            max_stack: usize,
+            gc_min_objects: usize,
+            gc_growth_trigger: f64,
+            ext_vars: HashMap<String, String>,
+            ext_codes: HashMap<String, String>,
+            tla_vars: HashMap<String, String>,
+            tla_codes: HashMap<String, String>,
+            max_trace: usize,
+            import_callback: Option<PyObject>,
+            native_callbacks: HashMap<String, (PyObject, PyObject)>,
+            preserve_order: bool,
+            manifest: &str,
+            indent: usize,
+            newline: bool,
+        ) -> PyResult<$ret> {
+            Jsonnet::build(
+                py,
+                jpathdir.map(|x| x.into_vec()),
+                max_stack,
+                gc_min_objects,
+                gc_growth_trigger,
+                ext_vars,
+                ext_codes,
+                tla_vars,
+                tla_codes,
+                max_trace,
+                import_callback,
+                native_callbacks,
+                preserve_order,
+                manifest,
+                indent,
+                newline,
+            )?
+            .$method(py, filename, src)
+        }
+    };
 }
 
+evaluate_file_pyfn!(evaluate_file, "Evaluate jsonnet file", String, evaluate_file);
+evaluate_snippet_pyfn!(
+    evaluate_snippet,
+    "Evaluate jsonnet code snippet",
+    String,
+    evaluate_snippet
+);
+evaluate_file_pyfn!(
+    evaluate_file_multi,
+    "Evaluate jsonnet file, manifesting each field of the top-level object independently",
+    PyObject,
+    evaluate_file_multi
+);
+evaluate_snippet_pyfn!(
+    evaluate_snippet_multi,
+    "Evaluate jsonnet code snippet, manifesting each field of the top-level object independently",
+    PyObject,
+    evaluate_snippet_multi
+);
+evaluate_file_pyfn!(
+    evaluate_file_stream,
+    "Evaluate jsonnet file, manifesting each element of the top-level array independently",
+    Vec<String>,
+    evaluate_file_stream
+);
+evaluate_snippet_pyfn!(
+    evaluate_snippet_stream,
+    "Evaluate jsonnet code snippet, manifesting each element of the top-level array independently",
+    Vec<String>,
+    evaluate_snippet_stream
+);
+
 /// Python bindings to Rust jrsonnet crate
 #[pymodule]
 fn rjsonnet(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<Jsonnet>()?;
+    m.add_class::<JsonnetFunc>()?;
     m.add_function(wrap_pyfunction!(evaluate_file, m)?)?;
     m.add_function(wrap_pyfunction!(evaluate_snippet, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_file_multi, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_snippet_multi, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_file_stream, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_snippet_stream, m)?)?;
     Ok(())
 }